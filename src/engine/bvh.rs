@@ -0,0 +1,138 @@
+use super::objects::traits::Intersectable;
+use super::objects::types::{Aabb, Intersection, Ray};
+
+enum BvhNode {
+    Leaf {
+        bounds: Aabb,
+        object_indices: Vec<usize>,
+    },
+    Interior {
+        bounds: Aabb,
+        axis: usize,
+        left: Box<BvhNode>,
+        right: Box<BvhNode>,
+    },
+}
+
+pub struct Bvh<'a> {
+    root: Option<BvhNode>,
+    objects: Vec<&'a (dyn Intersectable + Sync)>,
+}
+
+impl<'a> Bvh<'a> {
+    pub fn build(objects: Vec<&'a (dyn Intersectable + Sync)>) -> Self {
+        let indices: Vec<usize> = (0..objects.len()).collect();
+        let root = if indices.is_empty() {
+            None
+        } else {
+            Some(Self::build_node(&objects, indices))
+        };
+
+        Self { root, objects }
+    }
+
+    fn build_node(objects: &[&'a (dyn Intersectable + Sync)], indices: Vec<usize>) -> BvhNode {
+        let bounds = indices
+            .iter()
+            .map(|&i| objects[i].bounding_box())
+            .reduce(|a, b| a.union(&b))
+            .unwrap();
+
+        if indices.len() <= 2 {
+            return BvhNode::Leaf {
+                bounds,
+                object_indices: indices,
+            };
+        }
+
+        let extent = bounds.max - bounds.min;
+        let axis = if extent.x >= extent.y && extent.x >= extent.z {
+            0
+        } else if extent.y >= extent.z {
+            1
+        } else {
+            2
+        };
+
+        let mut sorted = indices;
+        sorted.sort_by(|&a, &b| {
+            let ca = objects[a].bounding_box().centroid()[axis];
+            let cb = objects[b].bounding_box().centroid()[axis];
+            ca.partial_cmp(&cb).unwrap()
+        });
+
+        let mid = sorted.len() / 2;
+        let right_indices = sorted.split_off(mid);
+        let left_indices = sorted;
+
+        let left = Self::build_node(objects, left_indices);
+        let right = Self::build_node(objects, right_indices);
+
+        BvhNode::Interior {
+            bounds,
+            axis,
+            left: Box::new(left),
+            right: Box::new(right),
+        }
+    }
+
+    pub fn intersect(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Intersection<'a>> {
+        match &self.root {
+            Some(node) => self.intersect_node(node, ray, t_min, t_max),
+            None => None,
+        }
+    }
+
+    fn intersect_node(
+        &self,
+        node: &BvhNode,
+        ray: &Ray,
+        t_min: f32,
+        t_max: f32,
+    ) -> Option<Intersection<'a>> {
+        match node {
+            BvhNode::Leaf {
+                bounds,
+                object_indices,
+            } => {
+                if !bounds.intersect(ray, t_min, t_max) {
+                    return None;
+                }
+
+                let mut closest: Option<Intersection<'a>> = None;
+                let mut closest_t = t_max;
+
+                for &idx in object_indices {
+                    if let Some(hit) = self.objects[idx].intersect(ray, t_min, closest_t) {
+                        closest_t = hit.distance;
+                        closest = Some(hit);
+                    }
+                }
+
+                closest
+            }
+            BvhNode::Interior {
+                bounds,
+                axis,
+                left,
+                right,
+            } => {
+                if !bounds.intersect(ray, t_min, t_max) {
+                    return None;
+                }
+
+                let (near, far) = if ray.direction[*axis] >= 0.0 {
+                    (left, right)
+                } else {
+                    (right, left)
+                };
+
+                let near_hit = self.intersect_node(near, ray, t_min, t_max);
+                let far_t_max = near_hit.as_ref().map(|hit| hit.distance).unwrap_or(t_max);
+                let far_hit = self.intersect_node(far, ray, t_min, far_t_max);
+
+                far_hit.or(near_hit)
+            }
+        }
+    }
+}
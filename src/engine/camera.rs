@@ -1,9 +1,15 @@
 use nalgebra::Vector3;
-use super::objects::types::{Ray, Intersection, LightSource};
-use super::objects::traits::{Intersectable};
+use super::objects::types::{Ray, Intersection, LightSource, LightKind, RenderMode};
+use super::objects::traits::Intersectable;
+use super::scene::Scene;
+use rand::Rng;
+use rayon::prelude::*;
 use std::fs::File;
 use std::io::Write;
 
+const PATH_TRACE_DEPTH: u32 = 8;
+const RUSSIAN_ROULETTE_START_DEPTH: u32 = PATH_TRACE_DEPTH - 3;
+
 pub struct Camera {
     origin: Vector3<f32>,
     forward: Vector3<f32>,
@@ -13,6 +19,9 @@ pub struct Camera {
     height: usize,
     width: usize,
     fov: f32,
+    lights: Vec<LightSource>,
+    samples_per_pixel: usize,
+    render_mode: RenderMode,
 }
 
 impl Camera {
@@ -24,7 +33,10 @@ impl Camera {
         distance: f32,
         height: usize,
         width: usize,
-        fov: f32, 
+        fov: f32,
+        lights: Vec<LightSource>,
+        samples_per_pixel: usize,
+        render_mode: RenderMode,
     ) -> Self {
         let forward = (look_at - origin).normalize();
         let right = forward.cross(&up_hint).normalize();
@@ -39,6 +51,9 @@ impl Camera {
             height,
             width,
             fov,
+            lights,
+            samples_per_pixel,
+            render_mode,
         }
     }
 }
@@ -55,33 +70,39 @@ impl Camera {
             * aspect_ratio;
 
         let mut rays = vec![];
+        let mut rng = rand::thread_rng();
 
         for j in 0..self.height {
             for i in 0..self.width {
-                let pixel_x = (i as f32 + 0.5)
-                    / self.width as f32;
-                let pixel_y = (j as f32 + 0.5)
-                    / self.height as f32;
-
-                let pixel_screen_x = (2.0 *
-                    pixel_x - 1.0) * image_plane_width
-                    / 2.0;
-                let pixel_screen_y = (1.0 -
-                    2.0 * pixel_y) * image_plane_height
-                    / 2.0;
-
-                let pixel_position = self.origin
-                    + self.forward * self.distance
-                    + self.right * pixel_screen_x
-                    + self.up * pixel_screen_y;
-
-                let ray_direction = (pixel_position - self.origin).normalize();
-                let ray =  Ray {
-                    origin: self.origin,
-                    direction: ray_direction,
-                };
-                
-                rays.push(ray);
+                for _ in 0..self.samples_per_pixel {
+                    let dx: f32 = rng.gen();
+                    let dy: f32 = rng.gen();
+
+                    let pixel_x = (i as f32 + dx)
+                        / self.width as f32;
+                    let pixel_y = (j as f32 + dy)
+                        / self.height as f32;
+
+                    let pixel_screen_x = (2.0 *
+                        pixel_x - 1.0) * image_plane_width
+                        / 2.0;
+                    let pixel_screen_y = (1.0 -
+                        2.0 * pixel_y) * image_plane_height
+                        / 2.0;
+
+                    let pixel_position = self.origin
+                        + self.forward * self.distance
+                        + self.right * pixel_screen_x
+                        + self.up * pixel_screen_y;
+
+                    let ray_direction = (pixel_position - self.origin).normalize();
+                    let ray =  Ray {
+                        origin: self.origin,
+                        direction: ray_direction,
+                    };
+
+                    rays.push(ray);
+                }
             }
         }
 
@@ -92,76 +113,135 @@ impl Camera {
     (
         &self,
         ray: &Ray,
-        objects: &Vec<Box<&dyn Intersectable>>,
+        scene: &Scene,
+        depth: u32,
+    ) -> (u8, u8, u8) {
+        match self.render_mode {
+            RenderMode::Direct => self.trace_direct(ray, scene, depth),
+            RenderMode::PathTraced => self.trace_path(ray, scene, PATH_TRACE_DEPTH),
+        }
+    }
+
+    fn trace_direct
+    (
+        &self,
+        ray: &Ray,
+        scene: &Scene,
         depth: u32,
     ) -> (u8, u8, u8) {
         if depth == 0 {
             return (0, 0, 0);
         }
 
-        let mut closest: Option<Intersection> = None;
-        let mut min_dist = f32::MAX;
-
-        for obj in objects  {
-            if let Some(hit) = obj.intersect(ray) {
-                if hit.distance < min_dist {
-                    min_dist = hit.distance;
-                    closest = Some(hit);
-                }
-            }
-        }
+        let closest = scene.closest_intersection(ray, 1e-3, f32::MAX);
 
         if let Some(hit) = closest {
-            let light = LightSource {
-                origin: Vector3::new(-5.0, 5.0, 5.0),
-                intensity: (255.0, 255.0, 255.0),
-                color: (255.0, 0.0, 100.0),
-            };
-
-            let light_dir = (light.origin - hit.point).normalize();
             let normal = hit.normal;
-            let product = normal.dot(&light_dir).max(0.0);
+            let base_color = hit.object.get_color();
 
-            let mut intensity = ((light.intensity.0 / 255.0 * product).clamp(0.0, 1.0),
-                (light.intensity.1 / 255.0 * product).clamp(0.0, 1.0),
-                (light.intensity.2 / 255.0 * product).clamp(0.0, 1.0));
+            let mut accumulated = (0.0f32, 0.0f32, 0.0f32);
+
+            for light in &self.lights {
+                let (light_dir, attenuation, light_distance) = match light.kind {
+                    LightKind::Point => {
+                        let to_light = light.origin - hit.point;
+                        let distance = to_light.norm();
+                        let attenuation = 1.0 / (distance * distance).max(1e-4);
+                        (to_light.normalize(), attenuation, distance)
+                    }
+                    LightKind::Directional => {
+                        (light.origin.normalize(), 1.0, f32::MAX)
+                    }
+                };
 
-            let light_color = ((light.color.0 / 255.0).clamp(0.0, 1.0),
-                (light.color.1 / 255.0).clamp(0.0, 1.0),
-                (light.color.2 / 255.0).clamp(0.0, 1.0)
-            );
+                let product = normal.dot(&light_dir).max(0.0);
 
-            let shadow_ray = Ray {
-                origin: hit.point + normal * 1e-3,
-                direction: light_dir,
-            };
+                let mut intensity = (
+                    (light.intensity.0 / 255.0 * product * attenuation).clamp(0.0, 1.0),
+                    (light.intensity.1 / 255.0 * product * attenuation).clamp(0.0, 1.0),
+                    (light.intensity.2 / 255.0 * product * attenuation).clamp(0.0, 1.0),
+                );
 
+                let light_color = ((light.color.0 / 255.0).clamp(0.0, 1.0),
+                    (light.color.1 / 255.0).clamp(0.0, 1.0),
+                    (light.color.2 / 255.0).clamp(0.0, 1.0)
+                );
 
-            let base_color = hit.object.get_color();
+                let shadow_ray = Ray {
+                    origin: hit.point + normal * 1e-3,
+                    direction: light_dir,
+                };
 
-            for obj in objects {
-                if let Some(shadow_hit) = obj.intersect(&shadow_ray) {
-                    if shadow_hit.distance > 1e-3 {
-                        intensity = (0.0, 0.0, 0.0);
-                        break;
-                    } 
+                if scene.closest_intersection(&shadow_ray, 1e-3, light_distance).is_some() {
+                    intensity = (0.0, 0.0, 0.0);
                 }
+
+                accumulated.0 += base_color.0 / 255.0 * intensity.0 * light_color.0;
+                accumulated.1 += base_color.1 / 255.0 * intensity.1 * light_color.1;
+                accumulated.2 += base_color.2 / 255.0 * intensity.2 * light_color.2;
             }
 
             let local_color = (
-                (base_color.0 as f32 / 255.0 * intensity.0).clamp(0.0, 1.0),
-                (base_color.1 as f32 / 255.0 * intensity.1).clamp(0.0, 1.0),
-                (base_color.2 as f32 / 255.0 * intensity.2).clamp(0.0, 1.0),
+                (accumulated.0.clamp(0.0, 1.0) * 255.0) as u8,
+                (accumulated.1.clamp(0.0, 1.0) * 255.0) as u8,
+                (accumulated.2.clamp(0.0, 1.0) * 255.0) as u8,
             );
 
-            let local_color = (
-                (local_color.0.clamp(0.0, 1.0) * light_color.0.clamp(0.0, 1.0) * 255.0) as u8,
-                (local_color.1.clamp(0.0, 1.0) * light_color.1.clamp(0.0, 1.0) * 255.0) as u8,
-                (local_color.2.clamp(0.0, 1.0) * light_color.2.clamp(0.0, 1.0) * 255.0) as u8,
-            );
+            let reflectivity = hit.object.reflectivity();
+            let transmissivity = hit.object.transmissivity();
 
+            if transmissivity > 0.0 {
+                let ior = hit.object.ior();
+                let entering = ray.direction.dot(&normal) < 0.0;
+                let (n1, n2) = if entering { (1.0, ior) } else { (ior, 1.0) };
 
-            let reflectivity = hit.object.reflectivity();
+                let cos_i = (-ray.direction.dot(&normal)).abs();
+                let r0 = ((n1 - n2) / (n1 + n2)).powi(2);
+                let fresnel = r0 + (1.0 - r0) * (1.0 - cos_i).powi(5);
+
+                let reflected_dir = self.reflect(
+                    &ray.direction, &normal
+                ).normalize();
+                let reflected_ray = Ray {
+                    origin: hit.point + normal * 1e-3,
+                    direction: reflected_dir,
+                };
+                let reflected_color = self.trace_direct(
+                    &reflected_ray, scene, depth - 1
+                );
+
+                let refracted = self.refract(&ray.direction, &normal, ior);
+
+                let (reflect_weight, transmit_weight) = match refracted {
+                    Some(_) => (transmissivity * fresnel, transmissivity * (1.0 - fresnel)),
+                    None => (transmissivity, 0.0),
+                };
+                let local_weight = 1.0 - reflect_weight - transmit_weight;
+
+                let transmitted_color = match refracted {
+                    Some(refracted_dir) => {
+                        let offset_normal = if entering { -normal } else { normal };
+                        let refracted_ray = Ray {
+                            origin: hit.point + offset_normal * 1e-3,
+                            direction: refracted_dir.normalize(),
+                        };
+                        self.trace_direct(&refracted_ray, scene, depth - 1)
+                    }
+                    None => (0, 0, 0),
+                };
+
+                return (
+                    (local_color.0 as f32 * local_weight
+                     + reflected_color.0 as f32 * reflect_weight
+                     + transmitted_color.0 as f32 * transmit_weight) as u8,
+                    (local_color.1 as f32 * local_weight
+                     + reflected_color.1 as f32 * reflect_weight
+                     + transmitted_color.1 as f32 * transmit_weight) as u8,
+                    (local_color.2 as f32 * local_weight
+                     + reflected_color.2 as f32 * reflect_weight
+                     + transmitted_color.2 as f32 * transmit_weight) as u8,
+                );
+            }
 
             if reflectivity > 0.0 {
                 let reflected_dir = self.reflect(
@@ -172,8 +252,8 @@ impl Camera {
                     direction: reflected_dir,
                 };
 
-                let reflected_color = self.trace_ray(
-                    &reflected_ray, objects, depth - 1
+                let reflected_color = self.trace_direct(
+                    &reflected_ray, scene, depth - 1
                 );
 
                 return (
@@ -192,40 +272,160 @@ impl Camera {
         (0, 0, 0)
     }
 
-    pub fn closest_intersection<'a>
+    fn trace_path
     (
         &self,
         ray: &Ray,
-        objects: &'a Vec<Box<&'a dyn Intersectable>>,
-    ) -> Option<(&'a dyn Intersectable, Intersection<'a>)> {
-        let mut closest_t = f32::MAX;
-        let mut result: Option<
+        scene: &Scene,
+        depth: u32,
+    ) -> (u8, u8, u8) {
+        if depth == 0 {
+            return (0, 0, 0);
+        }
+
+        let hit = match scene.closest_intersection(ray, 1e-3, f32::MAX) {
+            Some(hit) => hit,
+            None => return (0, 0, 0),
+        };
+
+        let normal = hit.normal;
+        let emission = hit.object.emission();
+
+        if hit.object.reflectivity() > 0.0 || hit.object.transmissivity() > 0.0 {
+            return self.trace_specular_path(ray, scene, &hit, depth);
+        }
+
+        let base_color = hit.object.get_color();
+        let albedo = (
+            base_color.0 / 255.0,
+            base_color.1 / 255.0,
+            base_color.2 / 255.0,
+        );
+        let max_albedo = albedo.0.max(albedo.1).max(albedo.2).max(1e-3);
+
+        let throughput = if depth <= RUSSIAN_ROULETTE_START_DEPTH {
+            if rand::random::<f32>() >= max_albedo {
+                return (
+                    emission.0.clamp(0.0, 255.0) as u8,
+                    emission.1.clamp(0.0, 255.0) as u8,
+                    emission.2.clamp(0.0, 255.0) as u8,
+                );
+            }
+            1.0 / max_albedo
+        } else {
+            1.0
+        };
+
+        let (u, v, n) = self.orthonormal_basis(&normal);
+
+        let r1: f32 = rand::random();
+        let r2: f32 = rand::random();
+        let phi = 2.0 * std::f32::consts::PI * r1;
+        let r = r2.sqrt();
+
+        let world_dir = (u * (r * phi.cos())
+            + v * (r * phi.sin())
+            + n * (1.0 - r2).sqrt()).normalize();
+
+        let bounce_ray = Ray {
+            origin: hit.point + normal * 1e-3,
+            direction: world_dir,
+        };
+
+        let incoming = self.trace_path(&bounce_ray, scene, depth - 1);
+
         (
-            &'a dyn Intersectable,
-            Intersection<'a>
-        )> = None;
-
-        for object in objects {
-            if let Some(intersection) =
-                object.intersect(&ray) {
-                let hit_point = intersection.point;
-                let normal = intersection.normal;
-                let t = intersection.distance;
-
-                if t < closest_t {
-                    closest_t = t;
-                    result = Some((**object, intersection));
+            (emission.0 + albedo.0 * incoming.0 as f32 * throughput).clamp(0.0, 255.0) as u8,
+            (emission.1 + albedo.1 * incoming.1 as f32 * throughput).clamp(0.0, 255.0) as u8,
+            (emission.2 + albedo.2 * incoming.2 as f32 * throughput).clamp(0.0, 255.0) as u8,
+        )
+    }
+
+    fn trace_specular_path
+    (
+        &self,
+        ray: &Ray,
+        scene: &Scene,
+        hit: &Intersection,
+        depth: u32,
+    ) -> (u8, u8, u8) {
+        let normal = hit.normal;
+        let emission = hit.object.emission();
+        let reflectivity = hit.object.reflectivity();
+        let transmissivity = hit.object.transmissivity();
+
+        if transmissivity > 0.0 {
+            let ior = hit.object.ior();
+            let entering = ray.direction.dot(&normal) < 0.0;
+            let (n1, n2) = if entering { (1.0, ior) } else { (ior, 1.0) };
+
+            let cos_i = (-ray.direction.dot(&normal)).abs();
+            let r0 = ((n1 - n2) / (n1 + n2)).powi(2);
+            let fresnel = r0 + (1.0 - r0) * (1.0 - cos_i).powi(5);
+
+            let reflected_dir = self.reflect(&ray.direction, &normal).normalize();
+            let reflected_ray = Ray {
+                origin: hit.point + normal * 1e-3,
+                direction: reflected_dir,
+            };
+            let reflected_color = self.trace_path(&reflected_ray, scene, depth - 1);
+
+            let refracted = self.refract(&ray.direction, &normal, ior);
+
+            let (reflect_weight, transmit_weight) = match refracted {
+                Some(_) => (fresnel, 1.0 - fresnel),
+                None => (1.0, 0.0),
+            };
+
+            let transmitted_color = match refracted {
+                Some(refracted_dir) => {
+                    let offset_normal = if entering { -normal } else { normal };
+                    let refracted_ray = Ray {
+                        origin: hit.point + offset_normal * 1e-3,
+                        direction: refracted_dir.normalize(),
+                    };
+                    self.trace_path(&refracted_ray, scene, depth - 1)
                 }
-            }
+                None => (0, 0, 0),
+            };
+
+            return (
+                (emission.0 + reflected_color.0 as f32 * reflect_weight
+                 + transmitted_color.0 as f32 * transmit_weight).clamp(0.0, 255.0) as u8,
+                (emission.1 + reflected_color.1 as f32 * reflect_weight
+                 + transmitted_color.1 as f32 * transmit_weight).clamp(0.0, 255.0) as u8,
+                (emission.2 + reflected_color.2 as f32 * reflect_weight
+                 + transmitted_color.2 as f32 * transmit_weight).clamp(0.0, 255.0) as u8,
+            );
         }
 
-        result
+        let reflected_dir = self.reflect(&ray.direction, &normal).normalize();
+        let reflected_ray = Ray {
+            origin: hit.point + normal * 1e-3,
+            direction: reflected_dir,
+        };
+        let reflected_color = self.trace_path(&reflected_ray, scene, depth - 1);
+
+        (
+            (emission.0 + reflected_color.0 as f32 * reflectivity).clamp(0.0, 255.0) as u8,
+            (emission.1 + reflected_color.1 as f32 * reflectivity).clamp(0.0, 255.0) as u8,
+            (emission.2 + reflected_color.2 as f32 * reflectivity).clamp(0.0, 255.0) as u8,
+        )
+    }
+
+    pub fn closest_intersection<'a>
+    (
+        &self,
+        ray: &Ray,
+        scene: &'a Scene<'a>,
+    ) -> Option<Intersection<'a>> {
+        scene.closest_intersection(ray, 1e-3, f32::MAX)
     }
 
     pub fn new_render
     (
         &self,
-        objects: &Vec<Box<&dyn Intersectable>>,
+        scene: &Scene,
         filename: &str
     ) {
         let mut file = File::create(filename)
@@ -236,12 +436,32 @@ impl Camera {
         writeln!(file, "255").unwrap();
 
         let rays = self.generate_rays();
+        let samples = self.samples_per_pixel;
 
-        for ray in &rays {
-            let color = self.trace_ray(
-                &ray, objects, 3);
+        let mut pixels: Vec<(u8, u8, u8)> = vec![(0, 0, 0); self.width * self.height];
+
+        pixels
+            .par_iter_mut()
+            .zip(rays.par_chunks(samples))
+            .for_each(|(pixel, pixel_rays)| {
+                let mut accumulated = (0.0f32, 0.0f32, 0.0f32);
+
+                for ray in pixel_rays {
+                    let color = self.trace_ray(ray, scene, 3);
+                    accumulated.0 += color.0 as f32;
+                    accumulated.1 += color.1 as f32;
+                    accumulated.2 += color.2 as f32;
+                }
 
+                let n = pixel_rays.len() as f32;
+                *pixel = (
+                    (accumulated.0 / n) as u8,
+                    (accumulated.1 / n) as u8,
+                    (accumulated.2 / n) as u8,
+                );
+            });
 
+        for color in &pixels {
             writeln!(file, "{} {} {}",
                 color.0, color.1, color.2).unwrap();
         }
@@ -256,4 +476,48 @@ impl Camera {
         incident - &(2.0 * (incident.dot(normal)) * normal)
     }
 
+    pub fn refract
+    (
+        &self,
+        incident: &Vector3<f32>,
+        normal: &Vector3<f32>,
+        ior: f32,
+    ) -> Option<Vector3<f32>> {
+        let mut cos_i = -incident.dot(normal);
+        let mut n = *normal;
+        let mut eta = 1.0 / ior;
+
+        if cos_i < 0.0 {
+            cos_i = -cos_i;
+            n = -normal;
+            eta = ior;
+        }
+
+        let k = 1.0 - eta * eta * (1.0 - cos_i * cos_i);
+
+        if k < 0.0 {
+            None
+        } else {
+            Some(eta * incident + (eta * cos_i - k.sqrt()) * n)
+        }
+    }
+
+    fn orthonormal_basis
+    (
+        &self,
+        normal: &Vector3<f32>,
+    ) -> (Vector3<f32>, Vector3<f32>, Vector3<f32>) {
+        let n = *normal;
+        let a = if n.x.abs() > 0.9 {
+            Vector3::new(0.0, 1.0, 0.0)
+        } else {
+            Vector3::new(1.0, 0.0, 0.0)
+        };
+
+        let u = a.cross(&n).normalize();
+        let v = n.cross(&u);
+
+        (u, v, n)
+    }
+
 }
\ No newline at end of file
@@ -1,10 +1,13 @@
-use super::types::{Ray, Intersection, Sphere, Plane};
+use nalgebra::Vector3;
+use super::types::{Aabb, Ray, Intersection, Sphere, Plane};
 
-pub trait Intersectable {
+pub trait Intersectable: Sync {
      fn intersect
     (
         &self,
-        ray: &Ray
+        ray: &Ray,
+        t_min: f32,
+        t_max: f32,
     ) -> Option<Intersection>;
 
      fn get_color
@@ -13,10 +16,18 @@ pub trait Intersectable {
     ) -> (f32, f32, f32);
 
      fn reflectivity(&self) -> f32;
+
+     fn transmissivity(&self) -> f32;
+
+     fn ior(&self) -> f32;
+
+     fn emission(&self) -> (f32, f32, f32);
+
+     fn bounding_box(&self) -> Aabb;
 }
 
 impl Intersectable for Sphere {
-    fn intersect(&self, ray: &Ray) -> Option<Intersection> {
+    fn intersect(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Intersection> {
         let oc = ray.origin - self.center;
 
         let a = ray.direction.dot(&ray.direction);
@@ -33,9 +44,9 @@ impl Intersectable for Sphere {
         let t1 = (-b - sqrt_d) / (2.0 * a);
         let t2 = (-b + sqrt_d) / (2.0 * a);
 
-        let t = if t1 >= 0.0 {
+        let t = if t1 > t_min && t1 < t_max {
             t1
-        } else if t2 >= 0.0 {
+        } else if t2 > t_min && t2 < t_max {
             t2
         } else {
             return None;
@@ -59,6 +70,26 @@ impl Intersectable for Sphere {
     fn reflectivity(&self) -> f32 {
         self.reflectivity
     }
+
+    fn transmissivity(&self) -> f32 {
+        self.transmissivity
+    }
+
+    fn ior(&self) -> f32 {
+        self.ior
+    }
+
+    fn emission(&self) -> (f32, f32, f32) {
+        self.emission
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let extent = Vector3::new(self.radius, self.radius, self.radius);
+        Aabb {
+            min: self.center - extent,
+            max: self.center + extent,
+        }
+    }
 }
 
 impl Intersectable for Plane {
@@ -67,6 +98,8 @@ impl Intersectable for Plane {
     (
         &self,
         ray: &Ray,
+        t_min: f32,
+        t_max: f32,
     ) -> Option<Intersection> {
         let denom = ray.direction.dot(&self.normal);
 
@@ -77,7 +110,7 @@ impl Intersectable for Plane {
         let t = (self.point - ray.origin).
                 dot(&self.normal) / denom;
 
-        if t < 0.0 {
+        if t <= t_min || t >= t_max {
             return None;
         }
 
@@ -97,4 +130,23 @@ impl Intersectable for Plane {
     fn reflectivity(&self) -> f32 {
         self.reflectivity
     }
+
+    fn transmissivity(&self) -> f32 {
+        self.transmissivity
+    }
+
+    fn ior(&self) -> f32 {
+        self.ior
+    }
+
+    fn emission(&self) -> (f32, f32, f32) {
+        self.emission
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        Aabb {
+            min: Vector3::new(f32::MIN, f32::MIN, f32::MIN),
+            max: Vector3::new(f32::MAX, f32::MAX, f32::MAX),
+        }
+    }
 }
\ No newline at end of file
@@ -6,6 +6,60 @@ pub struct Ray {
     pub direction: Vector3<f32>,
 }
 
+#[derive(Clone, Copy)]
+pub struct Aabb {
+    pub min: Vector3<f32>,
+    pub max: Vector3<f32>,
+}
+
+impl Aabb {
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Aabb {
+            min: Vector3::new(
+                self.min.x.min(other.min.x),
+                self.min.y.min(other.min.y),
+                self.min.z.min(other.min.z),
+            ),
+            max: Vector3::new(
+                self.max.x.max(other.max.x),
+                self.max.y.max(other.max.y),
+                self.max.z.max(other.max.z),
+            ),
+        }
+    }
+
+    pub fn centroid(&self) -> Vector3<f32> {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn intersect(&self, ray: &Ray, t_min: f32, t_max: f32) -> bool {
+        let mut t_min = t_min;
+        let mut t_max = t_max;
+
+        for axis in 0..3 {
+            let inv_dir = 1.0 / ray.direction[axis];
+
+            let (near, far) = if inv_dir >= 0.0 {
+                (self.min[axis], self.max[axis])
+            } else {
+                (self.max[axis], self.min[axis])
+            };
+
+            let t_near = (near - ray.origin[axis]) * inv_dir;
+            let t_far = (far - ray.origin[axis]) * inv_dir;
+
+            t_min = t_min.max(t_near);
+            t_max = t_max.min(t_far);
+
+            if t_min > t_max {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
 pub struct Intersection<'a> {
     pub point: Vector3<f32>,
     pub normal: Vector3<f32>,
@@ -13,10 +67,21 @@ pub struct Intersection<'a> {
     pub object: &'a dyn Intersectable
 }
 
+pub enum LightKind {
+    Point,
+    Directional,
+}
+
+pub enum RenderMode {
+    Direct,
+    PathTraced,
+}
+
 pub struct LightSource {
     pub origin: Vector3<f32>,
     pub intensity: (f32, f32, f32),
     pub color: (f32, f32, f32),
+    pub kind: LightKind,
 }
 
 pub struct Sphere {
@@ -24,6 +89,9 @@ pub struct Sphere {
     pub radius: f32,
     pub color: (f32, f32, f32),
     pub reflectivity: f32,
+    pub transmissivity: f32,
+    pub ior: f32,
+    pub emission: (f32, f32, f32),
 }
 
 pub struct Plane {
@@ -31,4 +99,7 @@ pub struct Plane {
     pub normal: Vector3<f32>,
     pub color: (f32, f32, f32),
     pub reflectivity: f32,
+    pub transmissivity: f32,
+    pub ior: f32,
+    pub emission: (f32, f32, f32),
 }
\ No newline at end of file
@@ -0,0 +1,39 @@
+use super::bvh::Bvh;
+use super::objects::traits::Intersectable;
+use super::objects::types::{Intersection, Ray};
+
+pub struct Scene<'a> {
+    bvh: Bvh<'a>,
+    unbounded: Vec<&'a (dyn Intersectable + Sync)>,
+}
+
+impl<'a> Scene<'a> {
+    pub fn new(
+        bounded: Vec<&'a (dyn Intersectable + Sync)>,
+        unbounded: Vec<&'a (dyn Intersectable + Sync)>,
+    ) -> Self {
+        Self {
+            bvh: Bvh::build(bounded),
+            unbounded,
+        }
+    }
+
+    pub fn closest_intersection(
+        &self,
+        ray: &Ray,
+        t_min: f32,
+        t_max: f32,
+    ) -> Option<Intersection<'a>> {
+        let mut closest = self.bvh.intersect(ray, t_min, t_max);
+        let mut closest_t = closest.as_ref().map(|hit| hit.distance).unwrap_or(t_max);
+
+        for object in &self.unbounded {
+            if let Some(hit) = object.intersect(ray, t_min, closest_t) {
+                closest_t = hit.distance;
+                closest = Some(hit);
+            }
+        }
+
+        closest
+    }
+}